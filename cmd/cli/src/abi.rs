@@ -0,0 +1,160 @@
+// C ABI for the "cffi shared library" distribution mode, compiled when this
+// crate is built with the `cffi` feature (see Cargo.toml). Python loads
+// `libcaddysnake` at runtime through cffi; the library has no pyo3/CPython
+// linkage, so one shared-library build covers every interpreter on a given
+// OS, not just CPython/PyPy via abi3. The server itself still runs as a
+// `caddy run` child process spawned by `supervisor` from the binary that
+// `supervisor::binary_path` locates; requests are not served in-process.
+//
+// This mirrors the `start`/`stop`/`reload`/`running` PyO3 functions in
+// `lib.rs`, but over a small C ABI: config comes in as a NUL-terminated
+// string, and failures are reported as a non-zero return code plus a
+// message retrievable through `caddysnake_last_error`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+
+use crate::supervisor::{self, ConfigInput};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|cell| {
+        *cell.borrow_mut() = CString::new(message).ok();
+    });
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|cell| {
+        *cell.borrow_mut() = None;
+    });
+}
+
+/// Returns the message from the most recent failed call on this thread, or
+/// a null pointer if the last call succeeded or none has been made yet.
+/// The returned pointer is valid until the next `caddysnake_*` call on the
+/// same thread.
+#[no_mangle]
+pub extern "C" fn caddysnake_last_error() -> *const c_char {
+    LAST_ERROR.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .map(|message| message.as_ptr())
+            .unwrap_or(std::ptr::null())
+    })
+}
+
+unsafe fn config_from_raw(config: *const c_char, is_json: c_int) -> Result<ConfigInput, String> {
+    if config.is_null() {
+        return Err("config must not be null".to_string());
+    }
+    let text = CStr::from_ptr(config)
+        .to_str()
+        .map_err(|e| format!("config is not valid UTF-8: {e}"))?
+        .to_string();
+    Ok(if is_json != 0 {
+        ConfigInput::Json(text)
+    } else {
+        ConfigInput::Caddyfile(text)
+    })
+}
+
+/// Starts the embedded caddy server. `config` is a NUL-terminated Caddyfile
+/// or JSON config string; `is_json` selects which. Returns 0 on success, -1
+/// on failure (see `caddysnake_last_error`).
+///
+/// # Safety
+/// `config` must be a valid pointer to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn caddysnake_start(config: *const c_char, is_json: c_int) -> c_int {
+    let result = config_from_raw(config, is_json)
+        .and_then(|config| supervisor::start(&supervisor::binary_path(None), config));
+    match result {
+        Ok(()) => {
+            clear_last_error();
+            0
+        }
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Stops the embedded caddy server. Returns 0 on success, -1 on failure
+/// (see `caddysnake_last_error`).
+#[no_mangle]
+pub extern "C" fn caddysnake_stop() -> c_int {
+    match supervisor::stop() {
+        Ok(()) => {
+            clear_last_error();
+            0
+        }
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Reloads the embedded caddy server's config without restarting it.
+/// Returns 0 on success, -1 on failure (see `caddysnake_last_error`).
+///
+/// # Safety
+/// `config` must be a valid pointer to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn caddysnake_config(config: *const c_char, is_json: c_int) -> c_int {
+    let result = config_from_raw(config, is_json)
+        .and_then(|config| supervisor::reload(&supervisor::binary_path(None), config));
+    match result {
+        Ok(()) => {
+            clear_last_error();
+            0
+        }
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Returns 1 if the embedded caddy server is running, 0 otherwise.
+#[no_mangle]
+pub extern "C" fn caddysnake_running() -> c_int {
+    clear_last_error();
+    supervisor::running() as c_int
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_from_raw_rejects_null() {
+        let err = unsafe { config_from_raw(std::ptr::null(), 0) }.unwrap_err();
+        assert!(err.contains("null"));
+    }
+
+    #[test]
+    fn config_from_raw_dispatches_on_is_json() {
+        let raw = CString::new("example.com").unwrap();
+        let caddyfile = unsafe { config_from_raw(raw.as_ptr(), 0) }.unwrap();
+        assert!(matches!(caddyfile, ConfigInput::Caddyfile(s) if s == "example.com"));
+
+        let raw = CString::new(r#"{"apps":{}}"#).unwrap();
+        let json = unsafe { config_from_raw(raw.as_ptr(), 1) }.unwrap();
+        assert!(matches!(json, ConfigInput::Json(s) if s == r#"{"apps":{}}"#));
+    }
+
+    #[test]
+    fn last_error_is_set_and_cleared() {
+        set_last_error("boom".to_string());
+        let message = unsafe { CStr::from_ptr(caddysnake_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "boom");
+
+        clear_last_error();
+        assert!(caddysnake_last_error().is_null());
+    }
+}
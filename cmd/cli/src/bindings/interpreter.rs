@@ -0,0 +1,34 @@
+// Interpreter detection for the abi3 wheel.
+//
+// A single `abi3` wheel is used for every CPython minor version and for
+// PyPy, so there is no interpreter-specific binary baked in at build time
+// the way a per-interpreter wheel would have. This module just identifies
+// which interpreter is loading the module so `supervisor` can fall back to
+// the generic bundled binary when an interpreter-specific one isn't present.
+
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Interpreter {
+    CPython,
+    PyPy,
+    Other,
+}
+
+impl Interpreter {
+    /// Reads `sys.implementation.name` to tell CPython, PyPy and anything
+    /// else apart. Falls back to `Other` if introspection fails for any
+    /// reason, since that's still safe to treat like an unknown interpreter.
+    pub(crate) fn current(py: Python<'_>) -> Self {
+        let name: PyResult<String> = (|| {
+            let sys = py.import_bound("sys")?;
+            let implementation = sys.getattr("implementation")?;
+            implementation.getattr("name")?.extract()
+        })();
+        match name.as_deref() {
+            Ok("cpython") => Interpreter::CPython,
+            Ok("pypy") => Interpreter::PyPy,
+            _ => Interpreter::Other,
+        }
+    }
+}
@@ -1,12 +1,105 @@
-// Minimal Rust library for maturin to build platform-specific wheels
-// This allows us to package the caddy binary without needing cibuildwheel
+// Rust library for maturin to build platform-specific wheels.
+// Packages and supervises the bundled caddy binary so it never needs
+// cibuildwheel, and exposes in-process lifecycle control to Python.
+//
+// Built against pyo3's `abi3` feature (see Cargo.toml), so maturin tags the
+// wheel `abi3-cpXY` and one upload covers every CPython minor version from
+// there on, plus PyPy via `interpreter::Interpreter::current`.
+//
+// This is the PyO3 "binary" distribution mode, which bundles a full
+// `caddy` executable and runs it as a child process. Building with
+// `--no-default-features --features cffi` instead compiles only the `abi`
+// module's C ABI as a `libcaddysnake` cdylib for Python's cffi to load at
+// runtime, with no pyo3/CPython linkage; see that module's doc comment for
+// when to prefer it. The two modes are mutually exclusive builds of this
+// same crate, selected by `scripts/build-wheels.sh`.
 
-use pyo3::prelude::*;
+#[cfg(feature = "cffi")]
+mod abi;
+mod supervisor;
 
-/// A simple Python module that provides access to the caddy binary
-#[pymodule]
-fn caddysnake(_py: Python, m: Bound<PyModule>) -> PyResult<()> {
-    // This is a minimal module - the actual functionality is in cli.py
-    // We just need this to satisfy maturin's requirements for a Rust module
-    Ok(())
+// pyo3's #[pyfunction]/#[pymodule] macros generate error-conversion code
+// that trips this lint on functions already returning `PyResult`; this is
+// a known pyo3/clippy interaction, not a real conversion in our code.
+#[cfg(feature = "binary")]
+#[allow(clippy::useless_conversion)]
+mod bindings {
+    mod interpreter;
+
+    use pyo3::exceptions::PyRuntimeError;
+    use pyo3::prelude::*;
+
+    use crate::supervisor::{self, ConfigInput};
+
+    impl ConfigInput {
+        fn from_py(py: Python<'_>, config: &Bound<'_, PyAny>) -> PyResult<Self> {
+            if let Ok(s) = config.extract::<String>() {
+                return Ok(ConfigInput::Caddyfile(s));
+            }
+            let json_mod = py.import_bound("json")?;
+            let dumped: String = json_mod.call_method1("dumps", (config,))?.extract()?;
+            Ok(ConfigInput::Json(dumped))
+        }
+    }
+
+    fn binary_path_for(py: Python<'_>) -> std::path::PathBuf {
+        let interpreter_dir = match interpreter::Interpreter::current(py) {
+            interpreter::Interpreter::CPython => Some("cpython"),
+            interpreter::Interpreter::PyPy => Some("pypy"),
+            interpreter::Interpreter::Other => None,
+        };
+        supervisor::binary_path(interpreter_dir)
+    }
+
+    /// Starts the embedded caddy server with the given config.
+    ///
+    /// `config` may be a Caddyfile (`str`) or a JSON config (`dict`), matching
+    /// the two formats `caddy run --config` already understands. Raises
+    /// `RuntimeError` if caddy is already running or fails to start.
+    #[pyfunction]
+    fn start(py: Python<'_>, config: &Bound<'_, PyAny>) -> PyResult<()> {
+        let config = ConfigInput::from_py(py, config)?;
+        let binary = binary_path_for(py);
+        py.allow_threads(|| supervisor::start(&binary, config))
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Stops the embedded caddy server started with `start()`.
+    ///
+    /// Raises `RuntimeError` if caddy was never started or has already been
+    /// stopped; a caddy process that already exited on its own is reaped
+    /// without an error.
+    #[pyfunction]
+    fn stop(py: Python<'_>) -> PyResult<()> {
+        py.allow_threads(supervisor::stop)
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Reloads the embedded caddy server's config without restarting it.
+    ///
+    /// `config` accepts the same shapes as `start()`. Raises `RuntimeError` if
+    /// caddy is not running or the reload fails.
+    #[pyfunction]
+    fn reload(py: Python<'_>, config: &Bound<'_, PyAny>) -> PyResult<()> {
+        let config = ConfigInput::from_py(py, config)?;
+        let binary = binary_path_for(py);
+        py.allow_threads(|| supervisor::reload(&binary, config))
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Returns whether the embedded caddy server is currently running.
+    #[pyfunction]
+    fn running() -> bool {
+        supervisor::running()
+    }
+
+    /// Python module that embeds and controls caddy in-process.
+    #[pymodule]
+    pub(crate) fn caddysnake(_py: Python, m: Bound<PyModule>) -> PyResult<()> {
+        m.add_function(wrap_pyfunction!(start, &m)?)?;
+        m.add_function(wrap_pyfunction!(stop, &m)?)?;
+        m.add_function(wrap_pyfunction!(reload, &m)?)?;
+        m.add_function(wrap_pyfunction!(running, &m)?)?;
+        Ok(())
+    }
 }
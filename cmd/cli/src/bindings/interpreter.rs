@@ -0,0 +1,93 @@
+// Interpreter detection for the abi3 wheel.
+//
+// A single `abi3` wheel is used for every CPython minor version and for
+// PyPy, so there is no interpreter-specific binary baked in at build time
+// the way a per-interpreter wheel would have. This module just identifies
+// which interpreter is loading the module so `supervisor` can fall back to
+// the generic bundled binary when a CPython-only one isn't present.
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Interpreter {
+    CPython,
+    PyPy,
+    Other,
+}
+
+/// The minimum `(major, minor)` CPython requires to satisfy the `abi3-py38`
+/// feature this crate is built with (see `Cargo.toml`). Bundling one abi3
+/// wheel per platform instead of one per CPython minor avoids the ABI
+/// mismatches version-tagged wheels are prone to when mixed, but only back
+/// to this floor.
+const MIN_ABI3_VERSION: (u8, u8) = (3, 8);
+
+fn supports_abi3(version: (u8, u8)) -> bool {
+    version >= MIN_ABI3_VERSION
+}
+
+impl Interpreter {
+    /// Reads `sys.implementation.name` to tell CPython, PyPy and anything
+    /// else apart. Falls back to `Other` if introspection fails for any
+    /// reason, since that's still safe to treat like an unknown interpreter.
+    pub(crate) fn current(py: Python<'_>) -> Self {
+        let name: PyResult<String> = (|| {
+            let sys = py.import_bound("sys")?;
+            let implementation = sys.getattr("implementation")?;
+            implementation.getattr("name")?.extract()
+        })();
+        match name.as_deref() {
+            Ok("cpython") => Interpreter::CPython,
+            Ok("pypy") => Interpreter::PyPy,
+            _ => Interpreter::Other,
+        }
+    }
+
+    /// Reads `sys.version_info[:2]` so callers can check ABI compatibility
+    /// without hardcoding how to introspect the interpreter.
+    fn version(py: Python<'_>) -> PyResult<(u8, u8)> {
+        let version_info = py.import_bound("sys")?.getattr("version_info")?;
+        Ok((
+            version_info.get_item(0)?.extract()?,
+            version_info.get_item(1)?.extract()?,
+        ))
+    }
+
+    /// Fails fast, at module import time, if the loading interpreter is
+    /// older than the `abi3-py38` floor this wheel is built against. A
+    /// version-tagged (cp38/cp39/...) wheel would instead simply not have
+    /// been selected by the resolver for an incompatible interpreter; abi3
+    /// bundles every CPython minor into one wheel, so we lose that free
+    /// check and have to make it explicit here to catch the segfault-prone
+    /// case of, e.g., loading this module under a stray Python 3.7.
+    pub(crate) fn check_abi_compatible(py: Python<'_>) -> PyResult<()> {
+        let version = Self::version(py)?;
+        if supports_abi3(version) {
+            return Ok(());
+        }
+        Err(PyRuntimeError::new_err(format!(
+            "caddysnake requires Python {}.{}+ (built with abi3-py38), but this \
+             interpreter reports {}.{}; upgrade the interpreter or install a \
+             caddysnake release built for it",
+            MIN_ABI3_VERSION.0, MIN_ABI3_VERSION.1, version.0, version.1
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supports_abi3_accepts_floor_and_above() {
+        assert!(supports_abi3((3, 8)));
+        assert!(supports_abi3((3, 12)));
+    }
+
+    #[test]
+    fn supports_abi3_rejects_below_floor() {
+        assert!(!supports_abi3((3, 7)));
+        assert!(!supports_abi3((2, 7)));
+    }
+}
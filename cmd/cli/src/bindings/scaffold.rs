@@ -0,0 +1,74 @@
+// Framework detection for `caddysnake init`-style Caddyfile scaffolding.
+//
+// This only looks at well-known entrypoint filenames on disk and renders the
+// `python` directive a user would otherwise have to write by hand; it has no
+// way to actually run the generated Caddyfile, since that needs the
+// `wsgi`/`asgi` subdirective support the pre-rewrite Go module implemented
+// and this tree doesn't (see `TRIAGE.md`).
+
+use std::path::Path;
+
+/// One `(entrypoint filename, subdirective, default target)` a detected
+/// project might use, checked in order so `manage.py` (Django) wins over a
+/// bare `wsgi.py`/`asgi.py` when both are present, since Django ships both.
+const CANDIDATES: &[(&str, &str, &str)] = &[
+    ("manage.py", "wsgi", "mysite.wsgi:application"),
+    ("asgi.py", "asgi", "asgi:app"),
+    ("wsgi.py", "wsgi", "wsgi:application"),
+];
+
+/// Returns the `(subdirective, target)` for the first known entrypoint found
+/// directly under `project_dir`, or `None` if none of `CANDIDATES` exist.
+pub(crate) fn detect(project_dir: &Path) -> Option<(&'static str, &'static str)> {
+    CANDIDATES
+        .iter()
+        .find(|(entrypoint, _, _)| project_dir.join(entrypoint).is_file())
+        .map(|(_, subdirective, target)| (*subdirective, *target))
+}
+
+/// Renders a minimal single-app Caddyfile block for `(subdirective, target)`
+/// as returned by `detect()`.
+pub(crate) fn render_caddyfile(subdirective: &str, target: &str) -> String {
+    format!("localhost {{\n    python {{\n        {subdirective} {target}\n    }}\n}}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "caddysnake-scaffold-test-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detect_prefers_manage_py_over_wsgi_py() {
+        let dir = scratch_dir("manage-wins");
+        std::fs::write(dir.join("manage.py"), "").unwrap();
+        std::fs::write(dir.join("wsgi.py"), "").unwrap();
+        assert_eq!(detect(&dir), Some(("wsgi", "mysite.wsgi:application")));
+    }
+
+    #[test]
+    fn detect_falls_back_to_asgi_py() {
+        let dir = scratch_dir("asgi-only");
+        std::fs::write(dir.join("asgi.py"), "").unwrap();
+        assert_eq!(detect(&dir), Some(("asgi", "asgi:app")));
+    }
+
+    #[test]
+    fn detect_returns_none_without_a_known_entrypoint() {
+        let dir = scratch_dir("no-entrypoint");
+        assert_eq!(detect(&dir), None);
+    }
+
+    #[test]
+    fn render_caddyfile_embeds_the_directive_and_target() {
+        let rendered = render_caddyfile("asgi", "main:app");
+        assert!(rendered.contains("asgi main:app"));
+    }
+}
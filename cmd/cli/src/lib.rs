@@ -1,12 +1,196 @@
-// Minimal Rust library for maturin to build platform-specific wheels
-// This allows us to package the caddy binary without needing cibuildwheel
+// Rust library for maturin to build platform-specific wheels.
+// Packages and supervises the bundled caddy binary so it never needs
+// cibuildwheel, and exposes in-process lifecycle control to Python.
+//
+// Built against pyo3's `abi3` feature (see Cargo.toml), so maturin tags the
+// wheel `abi3-cpXY` and one upload covers every CPython minor version from
+// there on, plus PyPy via `interpreter::Interpreter::current`. Since one
+// wheel now covers every minor instead of the resolver rejecting an
+// incompatible one for you, `caddysnake()` below checks the loading
+// interpreter against the abi3 floor itself via
+// `interpreter::Interpreter::check_abi_compatible`.
+//
+// This is the PyO3 "binary" distribution mode, which bundles a full
+// `caddy` executable and runs it as a child process. Building with
+// `--no-default-features --features cffi` instead compiles only the `abi`
+// module's C ABI as a `libcaddysnake` cdylib for Python's cffi to load at
+// runtime, with no pyo3/CPython linkage; see that module's doc comment for
+// when to prefer it. The two modes are mutually exclusive builds of this
+// same crate, selected by `scripts/build-wheels.sh`.
 
-use pyo3::prelude::*;
+#[cfg(feature = "cffi")]
+mod abi;
+mod supervisor;
 
-/// A simple Python module that provides access to the caddy binary
-#[pymodule]
-fn caddysnake(_py: Python, m: Bound<PyModule>) -> PyResult<()> {
-    // This is a minimal module - the actual functionality is in cli.py
-    // We just need this to satisfy maturin's requirements for a Rust module
-    Ok(())
+// pyo3's #[pyfunction]/#[pymodule] macros generate error-conversion code
+// that trips this lint on functions already returning `PyResult`; this is
+// a known pyo3/clippy interaction, not a real conversion in our code.
+#[cfg(feature = "binary")]
+#[allow(clippy::useless_conversion)]
+mod bindings {
+    mod interpreter;
+    mod scaffold;
+
+    use pyo3::exceptions::PyRuntimeError;
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    use crate::supervisor::{self, ConfigInput};
+
+    impl ConfigInput {
+        fn from_py(py: Python<'_>, config: &Bound<'_, PyAny>) -> PyResult<Self> {
+            if let Ok(s) = config.extract::<String>() {
+                return Ok(ConfigInput::Caddyfile(s));
+            }
+            let json_mod = py.import_bound("json")?;
+            let dumped: String = json_mod.call_method1("dumps", (config,))?.extract()?;
+            Ok(ConfigInput::Json(dumped))
+        }
+    }
+
+    fn binary_path_for(py: Python<'_>) -> std::path::PathBuf {
+        let interpreter_dir = match interpreter::Interpreter::current(py) {
+            interpreter::Interpreter::CPython => Some("cpython"),
+            interpreter::Interpreter::PyPy => Some("pypy"),
+            interpreter::Interpreter::Other => None,
+        };
+        supervisor::binary_path(interpreter_dir)
+    }
+
+    /// Starts the embedded caddy server with the given config.
+    ///
+    /// `config` may be a Caddyfile (`str`) or a JSON config (`dict`), matching
+    /// the two formats `caddy run --config` already understands. Raises
+    /// `RuntimeError` if caddy is already running or fails to start.
+    #[pyfunction]
+    fn start(py: Python<'_>, config: &Bound<'_, PyAny>) -> PyResult<()> {
+        let config = ConfigInput::from_py(py, config)?;
+        let binary = binary_path_for(py);
+        py.allow_threads(|| supervisor::start(&binary, config))
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Stops the embedded caddy server started with `start()`.
+    ///
+    /// Raises `RuntimeError` if caddy is not running.
+    #[pyfunction]
+    fn stop(py: Python<'_>) -> PyResult<()> {
+        py.allow_threads(supervisor::stop)
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Reloads the embedded caddy server's config without restarting it.
+    ///
+    /// `config` accepts the same shapes as `start()`. Raises `RuntimeError` if
+    /// caddy is not running or the reload fails.
+    #[pyfunction]
+    fn reload(py: Python<'_>, config: &Bound<'_, PyAny>) -> PyResult<()> {
+        let config = ConfigInput::from_py(py, config)?;
+        let binary = binary_path_for(py);
+        py.allow_threads(|| supervisor::reload(&binary, config))
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Returns whether the embedded caddy server is currently running.
+    #[pyfunction]
+    fn running() -> bool {
+        supervisor::running()
+    }
+
+    /// Starts caddy bound to `listen` with no Caddyfile needed, building the
+    /// minimal ad-hoc JSON config that requires (`{"apps": {"http": ...}}`)
+    /// and handing it to `start()` — the primitive a `caddysnake run --listen
+    /// :8000 ...` style CLI would build on. `apps` lets a caller merge in
+    /// extra top-level Caddy apps (e.g. `tls`) alongside the generated `http`
+    /// app; a key collision with `http` is an error rather than silently
+    /// overwriting the generated listener.
+    ///
+    /// Routing the `listen`ed server to an actual WSGI/ASGI app still needs
+    /// the app-hosting layer this crate doesn't implement (see
+    /// `TRIAGE.md`), so the generated config has no routes of its own.
+    #[pyfunction]
+    #[pyo3(signature = (listen, apps=None))]
+    fn quickstart(py: Python<'_>, listen: String, apps: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let http = PyDict::new_bound(py);
+        let servers = PyDict::new_bound(py);
+        let srv0 = PyDict::new_bound(py);
+        srv0.set_item("listen", vec![listen])?;
+        servers.set_item("srv0", srv0)?;
+        http.set_item("servers", servers)?;
+
+        let apps_config = PyDict::new_bound(py);
+        if let Some(apps) = apps {
+            for (key, value) in apps.downcast::<PyDict>()?.iter() {
+                apps_config.set_item(&key, value)?;
+            }
+        }
+        if apps_config.contains("http")? {
+            return Err(PyRuntimeError::new_err(
+                "quickstart() already generates the \"http\" app from `listen`; \
+                 pass other apps (e.g. \"tls\") in `apps` instead",
+            ));
+        }
+        apps_config.set_item("http", http)?;
+
+        let config = PyDict::new_bound(py);
+        config.set_item("apps", apps_config)?;
+        let dumped: String = py
+            .import_bound("json")?
+            .call_method1("dumps", (config,))?
+            .extract()?;
+
+        let binary = binary_path_for(py);
+        py.allow_threads(|| supervisor::start(&binary, ConfigInput::Json(dumped)))
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Detects which of `manage.py` (Django), `asgi.py`, or `wsgi.py`
+    /// exists directly under `project_dir` and renders a ready-to-run
+    /// single-app Caddyfile for it — the detection step a `caddysnake init`
+    /// subcommand would confirm with the user before writing a file.
+    /// Returns `None` if none of those entrypoints are present, so the
+    /// caller can ask the user to specify a target explicitly.
+    #[pyfunction]
+    fn scaffold_caddyfile(project_dir: &str) -> Option<String> {
+        let (subdirective, target) = scaffold::detect(std::path::Path::new(project_dir))?;
+        Some(scaffold::render_caddyfile(subdirective, target))
+    }
+
+    /// Runs `sys.executable` with `args` (e.g. `["manage.py", "migrate"]`)
+    /// in `cwd`, inheriting this process's stdio — the sidecar a
+    /// `caddysnake manage -- migrate` subcommand would wrap. Unlike the old
+    /// Go module, this crate runs inside whatever interpreter/venv called
+    /// it rather than bundling a separate one, so `sys.executable` already
+    /// *is* "the same environment caddy-snake would use to serve"; there is
+    /// no second interpreter to reach into. Returns the child's exit code;
+    /// raises `RuntimeError` if the process couldn't be spawned at all.
+    #[pyfunction]
+    #[pyo3(signature = (args, cwd=None))]
+    fn manage(py: Python<'_>, args: Vec<String>, cwd: Option<String>) -> PyResult<i32> {
+        let executable: String = py.import_bound("sys")?.getattr("executable")?.extract()?;
+        py.allow_threads(|| {
+            let mut command = std::process::Command::new(executable);
+            command.args(&args);
+            if let Some(cwd) = cwd {
+                command.current_dir(cwd);
+            }
+            command.status()
+        })
+        .map(|status| status.code().unwrap_or(-1))
+        .map_err(|e| PyRuntimeError::new_err(format!("failed to run manage command: {e}")))
+    }
+
+    /// Python module that embeds and controls caddy in-process.
+    #[pymodule]
+    pub(crate) fn caddysnake(py: Python, m: Bound<PyModule>) -> PyResult<()> {
+        interpreter::Interpreter::check_abi_compatible(py)?;
+        m.add_function(wrap_pyfunction!(start, &m)?)?;
+        m.add_function(wrap_pyfunction!(stop, &m)?)?;
+        m.add_function(wrap_pyfunction!(reload, &m)?)?;
+        m.add_function(wrap_pyfunction!(running, &m)?)?;
+        m.add_function(wrap_pyfunction!(quickstart, &m)?)?;
+        m.add_function(wrap_pyfunction!(scaffold_caddyfile, &m)?)?;
+        m.add_function(wrap_pyfunction!(manage, &m)?)?;
+        Ok(())
+    }
 }
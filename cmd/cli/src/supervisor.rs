@@ -0,0 +1,199 @@
+// Supervises a single embedded caddy process for the lifetime of the host
+// process. Previously `cli.py` shelled out to a detached `caddy run`
+// subprocess; here we keep a handle to the child so start/stop/reload can be
+// driven in-process and failures surface as real errors instead of a
+// silently-dangling process.
+//
+// This module is shared by both distribution modes: the PyO3 bindings in
+// `lib.rs` (binary mode, one bundled `caddy` executable per wheel) and the
+// C ABI in `abi.rs` (cffi mode, `libcaddysnake` loaded at runtime). Neither
+// mode is pyo3-specific here, so errors are plain `String`s that each
+// frontend converts to its own error type.
+
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+static SUPERVISOR: OnceLock<Mutex<Supervisor>> = OnceLock::new();
+
+#[derive(Default)]
+struct Supervisor {
+    child: Option<Child>,
+    config_path: Option<std::path::PathBuf>,
+}
+
+impl Supervisor {
+    fn is_running(&mut self) -> bool {
+        match &mut self.child {
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    fn spawn(
+        &mut self,
+        binary: &std::path::Path,
+        config_path: std::path::PathBuf,
+        adapter: &str,
+    ) -> std::io::Result<Child> {
+        Command::new(binary)
+            .arg("run")
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--adapter")
+            .arg(adapter)
+            .stdin(Stdio::null())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .inspect(|_| {
+                self.config_path = Some(config_path);
+            })
+    }
+}
+
+fn supervisor() -> &'static Mutex<Supervisor> {
+    SUPERVISOR.get_or_init(|| Mutex::new(Supervisor::default()))
+}
+
+/// Returns the path to the bundled caddy binary, honoring `CADDYSNAKE_BIN`
+/// for local development against a system-installed caddy.
+///
+/// `interpreter_dir` lets the abi3 binary-mode wheel prefer an
+/// interpreter-specific binary left over from an older per-interpreter
+/// wheel (`cpython`/`pypy`) before falling back to the generic one. The
+/// cffi shared-library mode has no such directory and always passes `None`,
+/// since one `libcaddysnake` build already covers every interpreter.
+pub(crate) fn binary_path(interpreter_dir: Option<&str>) -> std::path::PathBuf {
+    if let Ok(path) = std::env::var("CADDYSNAKE_BIN") {
+        return path.into();
+    }
+    let mut dir = std::env::current_exe().unwrap_or_default();
+    dir.pop();
+    let binary_name = if cfg!(windows) { "caddy.exe" } else { "caddy" };
+
+    if let Some(interpreter_dir) = interpreter_dir {
+        let candidate = dir.join(interpreter_dir).join(binary_name);
+        if candidate.exists() {
+            return candidate;
+        }
+    }
+
+    dir.join(binary_name)
+}
+
+fn write_config(config: &ConfigInput) -> Result<std::path::PathBuf, String> {
+    let path = std::env::temp_dir().join(format!("caddysnake-{}.json", std::process::id()));
+    let mut file = std::fs::File::create(&path)
+        .map_err(|e| format!("failed to write caddy config: {e}"))?;
+    file.write_all(config.as_str().as_bytes())
+        .map_err(|e| format!("failed to write caddy config: {e}"))?;
+    Ok(path)
+}
+
+/// Accepts either a Caddyfile or a JSON config, mirroring the two formats
+/// `caddy run --config` already understands.
+#[derive(Debug)]
+pub(crate) enum ConfigInput {
+    Caddyfile(String),
+    Json(String),
+}
+
+impl ConfigInput {
+    fn as_str(&self) -> &str {
+        match self {
+            ConfigInput::Caddyfile(s) => s,
+            ConfigInput::Json(s) => s,
+        }
+    }
+
+    /// The `--adapter` value `caddy run`/`caddy reload` need to parse this
+    /// config correctly.
+    fn adapter(&self) -> &'static str {
+        match self {
+            ConfigInput::Caddyfile(_) => "caddyfile",
+            ConfigInput::Json(_) => "json",
+        }
+    }
+}
+
+pub(crate) fn start(binary: &std::path::Path, config: ConfigInput) -> Result<(), String> {
+    let path = write_config(&config)?;
+    let mut sup = supervisor().lock().unwrap();
+    if sup.is_running() {
+        return Err("caddy is already running; call stop() or reload() first".to_string());
+    }
+    let child = sup
+        .spawn(binary, path, config.adapter())
+        .map_err(|e| format!("failed to start caddy: {e}"))?;
+    sup.child = Some(child);
+    Ok(())
+}
+
+pub(crate) fn stop() -> Result<(), String> {
+    let mut sup = supervisor().lock().unwrap();
+    let Some(mut child) = sup.child.take() else {
+        return Err("caddy is not running".to_string());
+    };
+    child
+        .kill()
+        .and_then(|_| child.wait().map(|_| ()))
+        .map_err(|e| format!("failed to stop caddy: {e}"))?;
+    sup.config_path = None;
+    Ok(())
+}
+
+pub(crate) fn reload(binary: &std::path::Path, config: ConfigInput) -> Result<(), String> {
+    let adapter = config.adapter();
+    let path = write_config(&config)?;
+    let mut sup = supervisor().lock().unwrap();
+    if !sup.is_running() {
+        return Err("caddy is not running; call start() first".to_string());
+    }
+    drop(sup);
+    let status = Command::new(binary)
+        .arg("reload")
+        .arg("--config")
+        .arg(&path)
+        .arg("--adapter")
+        .arg(adapter)
+        .status();
+    match status {
+        Ok(status) if status.success() => {
+            supervisor().lock().unwrap().config_path = Some(path);
+            Ok(())
+        }
+        Ok(status) => Err(format!("caddy reload exited with {status}")),
+        Err(e) => Err(format!("failed to reload caddy: {e}")),
+    }
+}
+
+pub(crate) fn running() -> bool {
+    supervisor().lock().unwrap().is_running()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapter_matches_config_shape() {
+        assert_eq!(ConfigInput::Caddyfile(String::new()).adapter(), "caddyfile");
+        assert_eq!(ConfigInput::Json(String::new()).adapter(), "json");
+    }
+
+    #[test]
+    fn binary_path_honors_env_override() {
+        std::env::set_var("CADDYSNAKE_BIN", "/tmp/custom-caddy");
+        let path = binary_path(Some("cpython"));
+        std::env::remove_var("CADDYSNAKE_BIN");
+        assert_eq!(path, std::path::Path::new("/tmp/custom-caddy"));
+    }
+
+    #[test]
+    fn binary_path_falls_back_when_interpreter_dir_missing() {
+        std::env::remove_var("CADDYSNAKE_BIN");
+        let path = binary_path(Some("nonexistent-interpreter-dir"));
+        assert!(!path.ends_with("nonexistent-interpreter-dir/caddy"));
+    }
+}